@@ -0,0 +1,9 @@
+use crate::id::MetricId;
+use crate::metrics::Metric;
+
+/// A `MetricsFilter` decides which metrics are included when
+/// `MetricsRegistry::snapshots()` is called, e.g. to drop noisy or
+/// high-cardinality series before they reach a reporter.
+pub trait MetricsFilter: Send + Sync {
+    fn accept(&self, id: &MetricId, metric: &Metric) -> bool;
+}
@@ -0,0 +1,152 @@
+use std::fmt::{self, Display, Formatter};
+
+#[cfg(feature = "ser")]
+use serde::{Serialize, Serializer};
+
+/// Identifies a metric by its name plus an optional set of key/value tags.
+///
+/// Two metrics with the same name but different tags (e.g. `http.request`
+/// with `{method="GET"}` vs `{method="POST"}`) are tracked as distinct
+/// entries in the registry. Tags are kept sorted by key so that `MetricId`s
+/// built from the same name/tag pairs in different orders compare and hash
+/// equal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MetricId {
+    name: String,
+    tags: Vec<(String, String)>,
+}
+
+impl MetricId {
+    /// Create a `MetricId` with no tags.
+    pub fn new<S: Into<String>>(name: S) -> MetricId {
+        MetricId {
+            name: name.into(),
+            tags: Vec::new(),
+        }
+    }
+
+    /// Create a `MetricId` with the given tags.
+    pub fn new_with_tags<S: Into<String>>(name: S, mut tags: Vec<(String, String)>) -> MetricId {
+        tags.sort_by(|a, b| a.0.cmp(&b.0));
+        MetricId {
+            name: name.into(),
+            tags,
+        }
+    }
+
+    /// The metric name, e.g. `http.request`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The tags attached to this metric, sorted by key.
+    pub fn tags(&self) -> &[(String, String)] {
+        &self.tags
+    }
+
+    /// Returns a new `MetricId` with the given tag added.
+    pub fn with_tag<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> MetricId {
+        self.tags.push((key.into(), value.into()));
+        self.tags.sort_by(|a, b| a.0.cmp(&b.0));
+        self
+    }
+}
+
+impl Display for MetricId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.tags.is_empty() {
+            write!(f, "{{")?;
+            for (i, (k, v)) in self.tags.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ",")?;
+                }
+                write!(f, "{}=\"{}\"", k, v)?;
+            }
+            write!(f, "}}")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<&str> for MetricId {
+    fn from(name: &str) -> MetricId {
+        MetricId::new(name)
+    }
+}
+
+impl From<String> for MetricId {
+    fn from(name: String) -> MetricId {
+        MetricId::new(name)
+    }
+}
+
+impl From<&MetricId> for MetricId {
+    fn from(id: &MetricId) -> MetricId {
+        id.clone()
+    }
+}
+
+impl<'a> From<(&'a str, &'a [(&'a str, &'a str)])> for MetricId {
+    fn from((name, tags): (&'a str, &'a [(&'a str, &'a str)])) -> MetricId {
+        let tags = tags
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        MetricId::new_with_tags(name, tags)
+    }
+}
+
+/// Lets a tag list be written as an array literal directly at the call site
+/// (e.g. `registry.meter(("http.request", &[("method", "GET")]))`) without
+/// the `&[...][..]` unsizing slice callers would otherwise need to spell out
+/// to satisfy the `&[(&str, &str)]` impl above.
+impl<'a, const N: usize> From<(&'a str, &'a [(&'a str, &'a str); N])> for MetricId {
+    fn from((name, tags): (&'a str, &'a [(&'a str, &'a str); N])) -> MetricId {
+        MetricId::from((name, &tags[..]))
+    }
+}
+
+#[cfg(feature = "ser")]
+impl Serialize for MetricId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MetricId;
+
+    #[test]
+    fn test_from_str() {
+        let id: MetricId = "http.request".into();
+        assert_eq!("http.request", id.name());
+        assert!(id.tags().is_empty());
+    }
+
+    #[test]
+    fn test_from_array_literal_tags() {
+        // Must compile and behave the same as the `&[...][..]` slice form,
+        // without callers needing to unsize the array themselves.
+        let id: MetricId = ("http.request", &[("method", "GET")]).into();
+        assert_eq!("http.request", id.name());
+        assert_eq!(&[("method".to_owned(), "GET".to_owned())], id.tags());
+    }
+
+    #[test]
+    fn test_tags_sorted_for_equality() {
+        let a = MetricId::new_with_tags(
+            "http.request",
+            vec![("region".to_owned(), "us".to_owned()), ("host".to_owned(), "a".to_owned())],
+        );
+        let b = MetricId::new_with_tags(
+            "http.request",
+            vec![("host".to_owned(), "a".to_owned()), ("region".to_owned(), "us".to_owned())],
+        );
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,10 @@
+pub mod filter;
+pub mod id;
+pub mod metrics;
+pub mod mset;
+pub mod registry;
+pub mod unit;
+
+pub use id::MetricId;
+pub use registry::MetricsRegistry;
+pub use unit::Unit;
@@ -0,0 +1,968 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "ser")]
+use serde::ser::SerializeMap;
+#[cfg(feature = "ser")]
+use serde::{Serialize, Serializer};
+
+const TICK_INTERVAL: Duration = Duration::from_secs(5);
+const M1_WINDOW: f64 = 60.0;
+const M5_WINDOW: f64 = 60.0 * 5.0;
+const M15_WINDOW: f64 = 60.0 * 15.0;
+
+/// Counter measures the number of occurrences of some state, it can increase
+/// and decrease.
+#[derive(Debug, Default)]
+pub struct Counter {
+    count: AtomicI64,
+    generation: AtomicU64,
+}
+
+impl Counter {
+    pub fn new() -> Counter {
+        Counter::default()
+    }
+
+    /// Increase the counter by 1.
+    pub fn inc(&self) {
+        self.inc_by(1);
+    }
+
+    /// Increase the counter by `n`.
+    pub fn inc_by(&self, n: i64) {
+        self.count.fetch_add(n, Ordering::Relaxed);
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Decrease the counter by 1.
+    pub fn dec(&self) {
+        self.dec_by(1);
+    }
+
+    /// Decrease the counter by `n`.
+    pub fn dec_by(&self, n: i64) {
+        self.count.fetch_sub(n, Ordering::Relaxed);
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Current value of the counter.
+    pub fn count(&self) -> i64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Monotonically increasing counter bumped on every write, used by
+    /// `MetricsRegistry::evict_idle` to detect recent activity.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Relaxed)
+    }
+}
+
+/// A function that produces the current value of a `Gauge` on demand.
+pub trait GaugeFn: Send + Sync {
+    fn value(&self) -> f64;
+}
+
+impl<F> GaugeFn for F
+where
+    F: Fn() -> f64 + Send + Sync,
+{
+    fn value(&self) -> f64 {
+        (self)()
+    }
+}
+
+/// Gauge reports a value computed by a user supplied function whenever a
+/// reporter asks for it, e.g. queue depth or number of open connections.
+pub struct Gauge {
+    func: Box<dyn GaugeFn>,
+}
+
+impl Gauge {
+    pub fn new(func: Box<dyn GaugeFn>) -> Gauge {
+        Gauge { func }
+    }
+
+    /// Compute and return the current value.
+    pub fn value(&self) -> f64 {
+        self.func.value()
+    }
+}
+
+impl Debug for Gauge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Gauge").finish()
+    }
+}
+
+/// An exponentially weighted moving average, ticked every `TICK_INTERVAL`,
+/// matching the Dropwizard Metrics `EWMA` so meters report rates that are
+/// comparable across implementations.
+#[derive(Debug)]
+struct Ewma {
+    alpha: f64,
+    uncounted: AtomicI64,
+    rate_per_sec_bits: AtomicU64,
+    initialized: std::sync::atomic::AtomicBool,
+}
+
+impl Ewma {
+    fn new(window_secs: f64) -> Ewma {
+        let alpha = 1.0 - (-TICK_INTERVAL.as_secs_f64() / window_secs).exp();
+        Ewma {
+            alpha,
+            uncounted: AtomicI64::new(0),
+            rate_per_sec_bits: AtomicU64::new(0),
+            initialized: std::sync::atomic::AtomicBool::new(false),
+        }
+    }
+
+    fn update(&self, n: i64) {
+        self.uncounted.fetch_add(n, Ordering::Relaxed);
+    }
+
+    fn tick(&self) {
+        let count = self.uncounted.swap(0, Ordering::Relaxed) as f64;
+        let instant_rate = count / TICK_INTERVAL.as_secs_f64();
+
+        if self.initialized.load(Ordering::Relaxed) {
+            let old_rate = f64::from_bits(self.rate_per_sec_bits.load(Ordering::Relaxed));
+            let new_rate = old_rate + self.alpha * (instant_rate - old_rate);
+            self.rate_per_sec_bits
+                .store(new_rate.to_bits(), Ordering::Relaxed);
+        } else {
+            self.rate_per_sec_bits
+                .store(instant_rate.to_bits(), Ordering::Relaxed);
+            self.initialized.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn rate_per_sec(&self) -> f64 {
+        f64::from_bits(self.rate_per_sec_bits.load(Ordering::Relaxed))
+    }
+}
+
+/// Meter measures the rate of an event and reports 1, 5 and 15 minute moving
+/// averages of it, similar to the load averages reported by `uptime` on
+/// Linux.
+#[derive(Debug)]
+pub struct Meter {
+    start: Instant,
+    last_tick_nanos: AtomicU64,
+    count: AtomicI64,
+    generation: AtomicU64,
+    m1: Ewma,
+    m5: Ewma,
+    m15: Ewma,
+}
+
+impl Default for Meter {
+    fn default() -> Meter {
+        Meter::new()
+    }
+}
+
+impl Meter {
+    pub fn new() -> Meter {
+        Meter {
+            start: Instant::now(),
+            last_tick_nanos: AtomicU64::new(0),
+            count: AtomicI64::new(0),
+            generation: AtomicU64::new(0),
+            m1: Ewma::new(M1_WINDOW),
+            m5: Ewma::new(M5_WINDOW),
+            m15: Ewma::new(M15_WINDOW),
+        }
+    }
+
+    /// Mark the occurrence of one event.
+    pub fn mark(&self) {
+        self.mark_n(1);
+    }
+
+    /// Mark the occurrence of `n` events.
+    pub fn mark_n(&self, n: i64) {
+        self.tick_if_necessary();
+        self.count.fetch_add(n, Ordering::Relaxed);
+        self.generation.fetch_add(1, Ordering::Relaxed);
+        self.m1.update(n);
+        self.m5.update(n);
+        self.m15.update(n);
+    }
+
+    /// Monotonically increasing counter bumped on every `mark`, used by
+    /// `MetricsRegistry::evict_idle` to detect recent activity.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    fn tick_if_necessary(&self) {
+        let tick_nanos = TICK_INTERVAL.as_nanos() as u64;
+        let old_tick = self.last_tick_nanos.load(Ordering::Relaxed);
+        let now = self.start.elapsed().as_nanos() as u64;
+        let age = now.saturating_sub(old_tick);
+
+        if age > tick_nanos {
+            let new_tick = now - (age % tick_nanos);
+            if self
+                .last_tick_nanos
+                .compare_exchange(old_tick, new_tick, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+            {
+                let required_ticks = age / tick_nanos;
+                for _ in 0..required_ticks {
+                    self.m1.tick();
+                    self.m5.tick();
+                    self.m15.tick();
+                }
+            }
+        }
+    }
+
+    /// Total number of events marked so far.
+    pub fn count(&self) -> i64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    pub fn one_minute_rate(&self) -> f64 {
+        self.tick_if_necessary();
+        self.m1.rate_per_sec()
+    }
+
+    pub fn five_minute_rate(&self) -> f64 {
+        self.tick_if_necessary();
+        self.m5.rate_per_sec()
+    }
+
+    pub fn fifteen_minute_rate(&self) -> f64 {
+        self.tick_if_necessary();
+        self.m15.rate_per_sec()
+    }
+}
+
+/// A point-in-time view over the values retained by a `Histogram`'s
+/// reservoir, used to compute statistics.
+pub struct Snapshot {
+    values: Vec<i64>,
+    /// Per-value retention weight, aligned with `values`. `None` for an
+    /// unweighted reservoir (e.g. `UniformReservoir`), in which case every
+    /// retained value is treated as equally likely. Set by
+    /// `ExpDecayReservoir` so `value()` can compute a quantile over the
+    /// weighted distribution rather than the plain retained sample, per the
+    /// Dropwizard/witchcraft forward-decay model.
+    weights: Option<Vec<f64>>,
+}
+
+impl Snapshot {
+    pub fn new(mut values: Vec<i64>) -> Snapshot {
+        values.sort_unstable();
+        Snapshot { values, weights: None }
+    }
+
+    /// Build a snapshot from `(value, weight)` pairs, as retained by
+    /// `ExpDecayReservoir`.
+    pub fn new_weighted(mut samples: Vec<(i64, f64)>) -> Snapshot {
+        samples.sort_by(|a, b| a.0.cmp(&b.0));
+        let values = samples.iter().map(|(v, _)| *v).collect();
+        let weights = samples.iter().map(|(_, w)| *w).collect();
+        Snapshot { values, weights: Some(weights) }
+    }
+
+    pub fn min(&self) -> i64 {
+        *self.values.first().unwrap_or(&0)
+    }
+
+    pub fn max(&self) -> i64 {
+        *self.values.last().unwrap_or(&0)
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.values.is_empty() {
+            return 0.0;
+        }
+        self.values.iter().sum::<i64>() as f64 / self.values.len() as f64
+    }
+
+    pub fn stddev(&self) -> f64 {
+        if self.values.len() < 2 {
+            return 0.0;
+        }
+        let mean = self.mean();
+        let variance = self
+            .values
+            .iter()
+            .map(|v| {
+                let diff = *v as f64 - mean;
+                diff * diff
+            })
+            .sum::<f64>()
+            / (self.values.len() - 1) as f64;
+        variance.sqrt()
+    }
+
+    /// Value at the given quantile, e.g. `0.99` for the 99th percentile.
+    pub fn value(&self, quantile: f64) -> f64 {
+        match &self.weights {
+            Some(weights) => self.weighted_value(quantile, weights),
+            None => self.unweighted_value(quantile),
+        }
+    }
+
+    fn unweighted_value(&self, quantile: f64) -> f64 {
+        if self.values.is_empty() {
+            return 0.0;
+        }
+        let pos = quantile * (self.values.len() as f64 + 1.0);
+        if pos < 1.0 {
+            return self.values[0] as f64;
+        }
+        if pos as usize >= self.values.len() {
+            return *self.values.last().unwrap() as f64;
+        }
+        let lower = self.values[pos as usize - 1] as f64;
+        let upper = self.values[pos as usize] as f64;
+        lower + (pos - pos.floor()) * (upper - lower)
+    }
+
+    /// Dropwizard/witchcraft `WeightedSnapshot`'s quantile algorithm: each
+    /// retained value is placed at the midpoint of its share of the
+    /// cumulative normalized weight, and the quantile looks up the value
+    /// whose midpoint straddles it. A value with a larger decay weight
+    /// (i.e. observed more recently) occupies a wider share and so is more
+    /// likely to be returned for a given quantile than an equally-retained
+    /// but older one.
+    fn weighted_value(&self, quantile: f64, weights: &[f64]) -> f64 {
+        if self.values.is_empty() {
+            return 0.0;
+        }
+        let sum_weight: f64 = weights.iter().sum();
+        if sum_weight <= 0.0 {
+            return self.unweighted_value(quantile);
+        }
+
+        let norm: Vec<f64> = weights.iter().map(|w| w / sum_weight).collect();
+        let mut midpoints = Vec::with_capacity(norm.len());
+        let mut previous = 0.0;
+        for (i, w) in norm.iter().enumerate() {
+            let midpoint = if i == 0 {
+                w / 2.0
+            } else {
+                previous + norm[i - 1] / 2.0 + w / 2.0
+            };
+            midpoints.push(midpoint);
+            previous = midpoint;
+        }
+
+        let pos = match midpoints.binary_search_by(|p| p.partial_cmp(&quantile).unwrap()) {
+            Ok(pos) => pos,
+            Err(pos) => pos,
+        };
+        if pos == 0 {
+            self.values[0] as f64
+        } else if pos >= self.values.len() {
+            *self.values.last().unwrap() as f64
+        } else {
+            self.values[pos] as f64
+        }
+    }
+}
+
+/// A reservoir retains a bounded, statistically representative sample of the
+/// values fed to a `Histogram`.
+pub trait Reservoir: Send + Sync {
+    fn update(&self, value: i64);
+    fn snapshot(&self) -> Snapshot;
+}
+
+/// Reservoir sampling (Vitter's Algorithm R) over a fixed-size window, giving
+/// every observed value an equal chance of being retained regardless of how
+/// long the process has been running.
+#[derive(Debug)]
+pub struct UniformReservoir {
+    values: Mutex<Vec<i64>>,
+    count: AtomicI64,
+    size: usize,
+}
+
+impl UniformReservoir {
+    /// Dropwizard Metrics' default reservoir size, offering a 99.9%
+    /// confidence level with a 5% margin of error.
+    pub const DEFAULT_SIZE: usize = 1028;
+
+    pub fn new(size: usize) -> UniformReservoir {
+        UniformReservoir {
+            values: Mutex::new(Vec::with_capacity(size)),
+            count: AtomicI64::new(0),
+            size,
+        }
+    }
+}
+
+impl Default for UniformReservoir {
+    fn default() -> UniformReservoir {
+        UniformReservoir::new(UniformReservoir::DEFAULT_SIZE)
+    }
+}
+
+impl Reservoir for UniformReservoir {
+    fn update(&self, value: i64) {
+        let c = self.count.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut values = self.values.lock().unwrap();
+
+        if (c as usize) <= self.size {
+            values.push(value);
+        } else {
+            let r = next_usize(c as usize);
+            if r < self.size {
+                values[r] = value;
+            }
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot::new(self.values.lock().unwrap().clone())
+    }
+}
+
+/// A small xorshift PRNG, good enough for reservoir sampling without taking
+/// a dependency on an external rand crate.
+fn next_u64() -> u64 {
+    use std::cell::Cell;
+
+    thread_local! {
+        static STATE: Cell<u64> = Cell::new(0x9E3779B97F4A7C15 ^ (&STATE as *const _ as u64));
+    }
+
+    STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        state.set(x);
+        x
+    })
+}
+
+fn next_usize(bound: usize) -> usize {
+    (next_u64() % bound as u64) as usize
+}
+
+/// A uniform random `f64` in `(0.0, 1.0)`, used by `ExpDecayReservoir` to
+/// turn a sample's decay weight into a priority.
+fn next_f64() -> f64 {
+    loop {
+        let v = (next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64);
+        if v > 0.0 {
+            return v;
+        }
+    }
+}
+
+/// Forward-decay reservoir (Cormode et al.), matching Dropwizard Metrics'
+/// `ExponentiallyDecayingReservoir`: each sample is assigned a priority
+/// weighted by `exp(alpha * (t - landmark))`, so recently observed values
+/// are exponentially more likely to be retained than old ones. The same
+/// decay weight is carried into `snapshot()` (see `Snapshot::new_weighted`),
+/// so quantiles are computed over the weighted distribution rather than
+/// treating every retained value as equally likely. This keeps a
+/// long-running histogram representative of the last few minutes of traffic
+/// instead of being biased toward whatever was sampled first.
+pub struct ExpDecayReservoir {
+    alpha: f64,
+    size: usize,
+    start: Instant,
+    state: Mutex<ExpDecayState>,
+}
+
+struct ExpDecayState {
+    // Keyed by the IEEE-754 bit pattern of the (always positive, finite)
+    // priority, which sorts the same as the float it represents.
+    values: BTreeMap<u64, WeightedSample>,
+    landmark_secs: f64,
+    next_rescale_secs: f64,
+}
+
+/// A retained value together with the decay weight it was inserted with
+/// (rescaled in lockstep with its priority so it always reads as
+/// `exp(alpha * (t - landmark_secs))` relative to the *current* landmark),
+/// used to compute a weighted quantile in `Snapshot::value`.
+struct WeightedSample {
+    value: i64,
+    weight: f64,
+}
+
+/// How often priorities are rescaled against a fresh landmark, matching
+/// Dropwizard's `ExponentiallyDecayingReservoir` so weights never run far
+/// enough from 1.0 to underflow or overflow an `f64`.
+const RESCALE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+impl ExpDecayReservoir {
+    pub fn new(size: usize, alpha: f64) -> ExpDecayReservoir {
+        ExpDecayReservoir {
+            alpha,
+            size,
+            start: Instant::now(),
+            state: Mutex::new(ExpDecayState {
+                values: BTreeMap::new(),
+                landmark_secs: 0.0,
+                next_rescale_secs: RESCALE_INTERVAL.as_secs_f64(),
+            }),
+        }
+    }
+
+    fn rescale(&self, state: &mut ExpDecayState, now_secs: f64) {
+        let factor = (-self.alpha * (now_secs - state.landmark_secs)).exp();
+        state.values = state
+            .values
+            .iter()
+            .map(|(&priority, sample)| {
+                let rescaled_priority = (f64::from_bits(priority) * factor).to_bits();
+                let rescaled_sample = WeightedSample {
+                    value: sample.value,
+                    weight: sample.weight * factor,
+                };
+                (rescaled_priority, rescaled_sample)
+            })
+            .collect();
+        state.landmark_secs = now_secs;
+        state.next_rescale_secs = now_secs + RESCALE_INTERVAL.as_secs_f64();
+    }
+}
+
+impl Reservoir for ExpDecayReservoir {
+    fn update(&self, value: i64) {
+        let now_secs = self.start.elapsed().as_secs_f64();
+        let mut state = self.state.lock().unwrap();
+
+        if now_secs >= state.next_rescale_secs {
+            self.rescale(&mut state, now_secs);
+        }
+
+        let weight = (self.alpha * (now_secs - state.landmark_secs)).exp();
+        let mut priority = (weight / next_f64()).to_bits();
+        while state.values.contains_key(&priority) {
+            priority = (weight / next_f64()).to_bits();
+        }
+        let sample = WeightedSample { value, weight };
+
+        if state.values.len() < self.size {
+            state.values.insert(priority, sample);
+        } else if let Some(&smallest) = state.values.keys().next() {
+            if smallest < priority {
+                state.values.remove(&smallest);
+                state.values.insert(priority, sample);
+            }
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let samples = self
+            .state
+            .lock()
+            .unwrap()
+            .values
+            .values()
+            .map(|sample| (sample.value, sample.weight))
+            .collect();
+        Snapshot::new_weighted(samples)
+    }
+}
+
+impl Debug for ExpDecayReservoir {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExpDecayReservoir")
+            .field("alpha", &self.alpha)
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+/// Selects which `Reservoir` implementation a histogram or timer should
+/// sample into, passed to
+/// [`crate::registry::MetricsRegistry::histogram_with_reservoir`] or
+/// [`crate::registry::MetricsRegistry::timer_with_reservoir`].
+#[derive(Debug, Clone, Copy)]
+pub enum ReservoirKind {
+    /// Reservoir sampling (Vitter's Algorithm R) over a fixed-size window.
+    /// This is the default used by `histogram()`/`timer()`.
+    Uniform { size: usize },
+    /// Forward-decay sampling that favors recently observed values. See
+    /// `ExpDecayReservoir`.
+    ExpDecay { size: usize, alpha: f64 },
+}
+
+impl ReservoirKind {
+    /// Dropwizard Metrics' default alpha for `ExponentiallyDecayingReservoir`.
+    pub const DEFAULT_ALPHA: f64 = 0.015;
+
+    pub fn build(self) -> Box<dyn Reservoir> {
+        match self {
+            ReservoirKind::Uniform { size } => Box::new(UniformReservoir::new(size)),
+            ReservoirKind::ExpDecay { size, alpha } => {
+                Box::new(ExpDecayReservoir::new(size, alpha))
+            }
+        }
+    }
+}
+
+impl Default for ReservoirKind {
+    fn default() -> ReservoirKind {
+        ReservoirKind::Uniform {
+            size: UniformReservoir::DEFAULT_SIZE,
+        }
+    }
+}
+
+/// Histogram measures the distribution of a series of values, reporting
+/// `min`, `max`, `mean`, `stddev` and arbitrary percentiles over the values
+/// retained by its reservoir.
+pub struct Histogram {
+    reservoir: Box<dyn Reservoir>,
+    count: AtomicI64,
+    sum: AtomicI64,
+    generation: AtomicU64,
+}
+
+impl Default for Histogram {
+    fn default() -> Histogram {
+        Histogram::new()
+    }
+}
+
+impl Histogram {
+    pub fn new() -> Histogram {
+        Histogram::with_reservoir(Box::new(UniformReservoir::default()))
+    }
+
+    pub fn with_reservoir(reservoir: Box<dyn Reservoir>) -> Histogram {
+        Histogram {
+            reservoir,
+            count: AtomicI64::new(0),
+            sum: AtomicI64::new(0),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    pub fn update(&self, value: i64) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(value, Ordering::Relaxed);
+        self.generation.fetch_add(1, Ordering::Relaxed);
+        self.reservoir.update(value);
+    }
+
+    pub fn count(&self) -> i64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Exact running sum of every value ever passed to `update`, independent
+    /// of the reservoir (which only retains a bounded sample). Unlike
+    /// `Snapshot::mean`, this isn't an estimate and so is safe to use for a
+    /// Prometheus-style `_sum` series.
+    pub fn sum(&self) -> i64 {
+        self.sum.load(Ordering::Relaxed)
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        self.reservoir.snapshot()
+    }
+
+    /// Monotonically increasing counter bumped on every `update`, used by
+    /// `MetricsRegistry::evict_idle` to detect recent activity.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Relaxed)
+    }
+}
+
+impl Debug for Histogram {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Histogram")
+            .field("count", &self.count())
+            .finish()
+    }
+}
+
+/// Timer is a combination of a `Meter` and a `Histogram`: the meter part
+/// tracks the rate events occur at and the histogram part tracks the
+/// distribution of time spent per event.
+#[derive(Debug)]
+pub struct Timer {
+    meter: Meter,
+    histogram: Histogram,
+}
+
+impl Default for Timer {
+    fn default() -> Timer {
+        Timer::new()
+    }
+}
+
+impl Timer {
+    pub fn new() -> Timer {
+        Timer::with_reservoir(Box::new(UniformReservoir::default()))
+    }
+
+    pub fn with_reservoir(reservoir: Box<dyn Reservoir>) -> Timer {
+        Timer {
+            meter: Meter::new(),
+            histogram: Histogram::with_reservoir(reservoir),
+        }
+    }
+
+    /// Record a single occurrence that took `duration`.
+    pub fn update(&self, duration: Duration) {
+        self.histogram.update(duration.as_nanos() as i64);
+        self.meter.mark();
+    }
+
+    /// Time a closure and record its duration.
+    pub fn time<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        let start = Instant::now();
+        let result = f();
+        self.update(start.elapsed());
+        result
+    }
+
+    pub fn count(&self) -> i64 {
+        self.meter.count()
+    }
+
+    /// Exact running sum of every duration ever passed to `update`, in
+    /// nanoseconds. See `Histogram::sum`.
+    pub fn sum(&self) -> i64 {
+        self.histogram.sum()
+    }
+
+    /// Monotonically increasing counter bumped on every `update`, used by
+    /// `MetricsRegistry::evict_idle` to detect recent activity.
+    pub fn generation(&self) -> u64 {
+        self.meter.generation()
+    }
+
+    pub fn one_minute_rate(&self) -> f64 {
+        self.meter.one_minute_rate()
+    }
+
+    pub fn five_minute_rate(&self) -> f64 {
+        self.meter.five_minute_rate()
+    }
+
+    pub fn fifteen_minute_rate(&self) -> f64 {
+        self.meter.fifteen_minute_rate()
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        self.histogram.snapshot()
+    }
+}
+
+/// A single metric held by the registry, wrapped so callers that only have
+/// an `&Metric` (e.g. filters, reporters) can match on its kind.
+#[derive(Debug, Clone)]
+pub enum Metric {
+    Counter(std::sync::Arc<Counter>),
+    Gauge(std::sync::Arc<Gauge>),
+    Meter(std::sync::Arc<Meter>),
+    Histogram(std::sync::Arc<Histogram>),
+    Timer(std::sync::Arc<Timer>),
+}
+
+impl Metric {
+    /// Generation counter bumped on every write, or `None` for `Gauge`
+    /// (whose value is pulled on demand rather than pushed), used by
+    /// `MetricsRegistry::evict_idle` to detect recent activity.
+    pub fn generation(&self) -> Option<u64> {
+        match self {
+            Metric::Counter(c) => Some(c.generation()),
+            Metric::Gauge(_) => None,
+            Metric::Meter(m) => Some(m.generation()),
+            Metric::Histogram(h) => Some(h.generation()),
+            Metric::Timer(t) => Some(t.generation()),
+        }
+    }
+
+    /// Number of `Arc` handles pointing at the underlying metric, including
+    /// the one held by the registry itself. A value greater than 1 means a
+    /// caller outside the registry still holds a reference.
+    pub fn strong_count(&self) -> usize {
+        match self {
+            Metric::Counter(c) => std::sync::Arc::strong_count(c),
+            Metric::Gauge(g) => std::sync::Arc::strong_count(g),
+            Metric::Meter(m) => std::sync::Arc::strong_count(m),
+            Metric::Histogram(h) => std::sync::Arc::strong_count(h),
+            Metric::Timer(t) => std::sync::Arc::strong_count(t),
+        }
+    }
+}
+
+/// A metric together with the optional unit and description attached when
+/// it was created, as stored in the registry.
+#[derive(Debug, Clone)]
+pub struct MetricEntry {
+    pub metric: Metric,
+    pub unit: Option<crate::unit::Unit>,
+    pub description: Option<String>,
+}
+
+impl MetricEntry {
+    pub fn new(metric: Metric) -> MetricEntry {
+        MetricEntry {
+            metric,
+            unit: None,
+            description: None,
+        }
+    }
+
+    pub fn with_metadata(
+        metric: Metric,
+        unit: Option<crate::unit::Unit>,
+        description: Option<String>,
+    ) -> MetricEntry {
+        MetricEntry {
+            metric,
+            unit,
+            description,
+        }
+    }
+}
+
+#[cfg(feature = "ser")]
+impl Serialize for MetricEntry {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        if let Some(unit) = &self.unit {
+            map.serialize_entry("unit", unit.as_canonical_label())?;
+        }
+        if let Some(description) = &self.description {
+            map.serialize_entry("description", description)?;
+        }
+        map.serialize_entry("metric", &self.metric)?;
+        map.end()
+    }
+}
+
+#[cfg(feature = "ser")]
+impl Serialize for Metric {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Metric::Counter(c) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("count", &c.count())?;
+                map.end()
+            }
+            Metric::Gauge(g) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("value", &g.value())?;
+                map.end()
+            }
+            Metric::Meter(m) => {
+                let mut map = serializer.serialize_map(Some(4))?;
+                map.serialize_entry("count", &m.count())?;
+                map.serialize_entry("m1", &m.one_minute_rate())?;
+                map.serialize_entry("m5", &m.five_minute_rate())?;
+                map.serialize_entry("m15", &m.fifteen_minute_rate())?;
+                map.end()
+            }
+            Metric::Histogram(h) => {
+                let snapshot = h.snapshot();
+                let mut map = serializer.serialize_map(Some(6))?;
+                map.serialize_entry("count", &h.count())?;
+                map.serialize_entry("min", &snapshot.min())?;
+                map.serialize_entry("max", &snapshot.max())?;
+                map.serialize_entry("mean", &snapshot.mean())?;
+                map.serialize_entry("stddev", &snapshot.stddev())?;
+                map.serialize_entry("p99", &snapshot.value(0.99))?;
+                map.end()
+            }
+            Metric::Timer(t) => {
+                let snapshot = t.snapshot();
+                let mut map = serializer.serialize_map(Some(9))?;
+                map.serialize_entry("count", &t.count())?;
+                map.serialize_entry("m1", &t.one_minute_rate())?;
+                map.serialize_entry("m5", &t.five_minute_rate())?;
+                map.serialize_entry("m15", &t.fifteen_minute_rate())?;
+                map.serialize_entry("min", &snapshot.min())?;
+                map.serialize_entry("max", &snapshot.max())?;
+                map.serialize_entry("mean", &snapshot.mean())?;
+                map.serialize_entry("stddev", &snapshot.stddev())?;
+                map.serialize_entry("p99", &snapshot.value(0.99))?;
+                map.end()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_counter() {
+        let counter = Counter::new();
+        counter.inc();
+        counter.inc_by(2);
+        counter.dec();
+        assert_eq!(2, counter.count());
+    }
+
+    #[test]
+    fn test_meter() {
+        let meter = Meter::new();
+        meter.mark_n(5);
+        assert_eq!(5, meter.count());
+    }
+
+    #[test]
+    fn test_histogram() {
+        let histogram = Histogram::new();
+        for i in 1..=100 {
+            histogram.update(i);
+        }
+        let snapshot = histogram.snapshot();
+        assert_eq!(100, histogram.count());
+        assert_eq!(1, snapshot.min());
+        assert_eq!(100, snapshot.max());
+        assert_eq!(5050, histogram.sum());
+    }
+
+    #[test]
+    fn test_exp_decay_reservoir() {
+        let histogram = Histogram::with_reservoir(
+            ReservoirKind::ExpDecay {
+                size: 10,
+                alpha: ReservoirKind::DEFAULT_ALPHA,
+            }
+            .build(),
+        );
+        for i in 1..=100 {
+            histogram.update(i);
+        }
+
+        let snapshot = histogram.snapshot();
+        assert_eq!(100, histogram.count());
+        assert!(snapshot.min() >= 1);
+        assert!(snapshot.max() <= 100);
+    }
+
+    #[test]
+    fn test_weighted_snapshot_favors_heavier_samples() {
+        // A low value carries almost all the weight here, so quantiles
+        // comfortably inside its share of the cumulative distribution
+        // should resolve to it rather than splitting evenly the way an
+        // unweighted two-value snapshot would.
+        let snapshot = Snapshot::new_weighted(vec![(1, 100.0), (1000, 0.01)]);
+        assert_eq!(1.0, snapshot.value(0.1));
+        assert_eq!(1000.0, snapshot.value(0.9999));
+    }
+}
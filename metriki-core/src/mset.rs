@@ -0,0 +1,12 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use crate::id::MetricId;
+use crate::metrics::Metric;
+
+/// A `MetricsSet` returns a set of metrics when `snapshots()` is called on
+/// the registry. This provides dynamic metrics that can be added into the
+/// registry based on custom rules, e.g. metrics scraped from the OS.
+pub trait MetricsSet: Send + Sync + Debug {
+    fn get_all(&self) -> HashMap<MetricId, Metric>;
+}
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 use std::fmt::{self, Debug, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[cfg(feature = "ser")]
 use serde::ser::SerializeMap;
@@ -8,8 +11,17 @@ use serde::ser::SerializeMap;
 use serde::{Serialize, Serializer};
 
 use crate::filter::MetricsFilter;
+use crate::id::MetricId;
 use crate::metrics::*;
 use crate::mset::MetricsSet;
+use crate::unit::Unit;
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
 
 /// Entrypoint of all metrics
 ///
@@ -29,10 +41,34 @@ impl Debug for MetricsRegistry {
 
 #[derive(Default, Debug)]
 struct Inner {
-    metrics: HashMap<String, Metric>,
+    metrics: HashMap<MetricId, Tracked>,
     mset: HashMap<String, Arc<dyn MetricsSet + 'static>>,
 }
 
+/// A `MetricEntry` plus the bookkeeping `MetricsRegistry::evict_idle` needs
+/// to tell a quiet-but-still-referenced metric apart from an actually idle
+/// one, following the witchcraft/metrics-util `Recency` pattern: a metric is
+/// only idle once its write generation has stayed unchanged for at least the
+/// timeout since the baseline, which is taken when the metric is created and
+/// retaken by every `evict_idle` call that sees the generation change.
+#[derive(Debug)]
+struct Tracked {
+    entry: MetricEntry,
+    last_generation: AtomicU64,
+    idle_since_millis: AtomicU64,
+}
+
+impl Tracked {
+    fn new(entry: MetricEntry) -> Tracked {
+        let generation = entry.metric.generation().unwrap_or(0);
+        Tracked {
+            entry,
+            last_generation: AtomicU64::new(generation),
+            idle_since_millis: AtomicU64::new(now_millis()),
+        }
+    }
+}
+
 impl MetricsRegistry {
     /// Create a default metrics registry
     pub fn new() -> MetricsRegistry {
@@ -49,14 +85,30 @@ impl MetricsRegistry {
     /// Meter a metric to measure rate of an event. It will report rate in 1 minute,
     /// 5 minutes and 15 minutes, which is similar to Linux load.
     ///
+    /// `id` accepts anything convertible into a `MetricId`, so a plain name
+    /// (`"http.request"`) and a name with tags (`("http.request", &[("method", "GET")])`)
+    /// both work.
+    ///
     /// # Panics
     ///
     /// This function may panic if a metric is already registered with type other than meter.
-    pub fn meter(&self, name: &str) -> Arc<Meter> {
+    pub fn meter<T: Into<MetricId>>(&self, id: T) -> Arc<Meter> {
+        self.meter_with(id, None, None)
+    }
+
+    /// Like [`MetricsRegistry::meter`], but attaches a `Unit` and description
+    /// to the metric the first time it is created.
+    pub fn meter_with<T: Into<MetricId>>(
+        &self,
+        id: T,
+        unit: Option<Unit>,
+        description: Option<&str>,
+    ) -> Arc<Meter> {
+        let id = id.into();
         let meter = {
             let inner = self.inner.read().unwrap();
 
-            inner.metrics.get(name).map(|metric| match metric {
+            inner.metrics.get(&id).map(|tracked| match &tracked.entry.metric {
                 Metric::Meter(ref m) => m.clone(),
                 _ => panic!("A metric with same name and different type is already registered."),
             })
@@ -67,9 +119,12 @@ impl MetricsRegistry {
         } else {
             let mut inner_write = self.inner.write().unwrap();
             let meter = Arc::new(Meter::new());
-            inner_write
-                .metrics
-                .insert(name.to_owned(), Metric::Meter(meter.clone()));
+            let entry = MetricEntry::with_metadata(
+                Metric::Meter(meter.clone()),
+                unit,
+                description.map(|d| d.to_owned()),
+            );
+            inner_write.metrics.insert(id, Tracked::new(entry));
             meter
         }
     }
@@ -82,11 +137,23 @@ impl MetricsRegistry {
     /// # Panics
     ///
     /// This function may panic if a metric is already registered with type other than histogram.
-    pub fn histogram(&self, name: &str) -> Arc<Histogram> {
+    pub fn histogram<T: Into<MetricId>>(&self, id: T) -> Arc<Histogram> {
+        self.histogram_with(id, None, None)
+    }
+
+    /// Like [`MetricsRegistry::histogram`], but attaches a `Unit` and
+    /// description to the metric the first time it is created.
+    pub fn histogram_with<T: Into<MetricId>>(
+        &self,
+        id: T,
+        unit: Option<Unit>,
+        description: Option<&str>,
+    ) -> Arc<Histogram> {
+        let id = id.into();
         let histo = {
             let inner = self.inner.read().unwrap();
 
-            inner.metrics.get(name).map(|metric| match metric {
+            inner.metrics.get(&id).map(|tracked| match &tracked.entry.metric {
                 Metric::Histogram(ref m) => m.clone(),
                 _ => panic!("A metric with same name and different type is already registered."),
             })
@@ -97,9 +164,51 @@ impl MetricsRegistry {
         } else {
             let mut inner_write = self.inner.write().unwrap();
             let histo = Arc::new(Histogram::new());
-            inner_write
-                .metrics
-                .insert(name.to_owned(), Metric::Histogram(histo.clone()));
+            let entry = MetricEntry::with_metadata(
+                Metric::Histogram(histo.clone()),
+                unit,
+                description.map(|d| d.to_owned()),
+            );
+            inner_write.metrics.insert(id, Tracked::new(entry));
+            histo
+        }
+    }
+
+    /// Like [`MetricsRegistry::histogram_with`], but also selects the
+    /// `Reservoir` implementation the histogram samples into when it is
+    /// first created, e.g.
+    /// `registry.histogram_with_reservoir("latency", ReservoirKind::ExpDecay { size: 1028, alpha: ReservoirKind::DEFAULT_ALPHA }, None, None)`.
+    ///
+    /// Has no effect if a histogram with this id already exists, since the
+    /// reservoir is fixed at creation time.
+    pub fn histogram_with_reservoir<T: Into<MetricId>>(
+        &self,
+        id: T,
+        reservoir: ReservoirKind,
+        unit: Option<Unit>,
+        description: Option<&str>,
+    ) -> Arc<Histogram> {
+        let id = id.into();
+        let histo = {
+            let inner = self.inner.read().unwrap();
+
+            inner.metrics.get(&id).map(|tracked| match &tracked.entry.metric {
+                Metric::Histogram(ref m) => m.clone(),
+                _ => panic!("A metric with same name and different type is already registered."),
+            })
+        };
+
+        if let Some(m) = histo {
+            m
+        } else {
+            let mut inner_write = self.inner.write().unwrap();
+            let histo = Arc::new(Histogram::with_reservoir(reservoir.build()));
+            let entry = MetricEntry::with_metadata(
+                Metric::Histogram(histo.clone()),
+                unit,
+                description.map(|d| d.to_owned()),
+            );
+            inner_write.metrics.insert(id, Tracked::new(entry));
             histo
         }
     }
@@ -111,11 +220,24 @@ impl MetricsRegistry {
     /// # Panics
     ///
     /// This function may panic if a metric is already registered with type other than counter.
-    pub fn counter(&self, name: &str) -> Arc<Counter> {
+    pub fn counter<T: Into<MetricId>>(&self, id: T) -> Arc<Counter> {
+        self.counter_with(id, None, None)
+    }
+
+    /// Like [`MetricsRegistry::counter`], but attaches a `Unit` and
+    /// description to the metric the first time it is created, e.g.
+    /// `registry.counter_with("db.bytes", Some(Unit::Bytes), Some("bytes written"))`.
+    pub fn counter_with<T: Into<MetricId>>(
+        &self,
+        id: T,
+        unit: Option<Unit>,
+        description: Option<&str>,
+    ) -> Arc<Counter> {
+        let id = id.into();
         let counter = {
             let inner = self.inner.read().unwrap();
 
-            inner.metrics.get(name).map(|metric| match metric {
+            inner.metrics.get(&id).map(|tracked| match &tracked.entry.metric {
                 Metric::Counter(ref m) => m.clone(),
                 _ => panic!("A metric with same name and different type is already registered."),
             })
@@ -126,9 +248,12 @@ impl MetricsRegistry {
         } else {
             let mut inner_write = self.inner.write().unwrap();
             let counter = Arc::new(Counter::new());
-            inner_write
-                .metrics
-                .insert(name.to_owned(), Metric::Counter(counter.clone()));
+            let entry = MetricEntry::with_metadata(
+                Metric::Counter(counter.clone()),
+                unit,
+                description.map(|d| d.to_owned()),
+            );
+            inner_write.metrics.insert(id, Tracked::new(entry));
             counter
         }
     }
@@ -141,11 +266,23 @@ impl MetricsRegistry {
     /// # Panics
     ///
     /// This function may panic if a metric is already registered with type other than counter.
-    pub fn timer(&self, name: &str) -> Arc<Timer> {
+    pub fn timer<T: Into<MetricId>>(&self, id: T) -> Arc<Timer> {
+        self.timer_with(id, None, None)
+    }
+
+    /// Like [`MetricsRegistry::timer`], but attaches a `Unit` and description
+    /// to the metric the first time it is created.
+    pub fn timer_with<T: Into<MetricId>>(
+        &self,
+        id: T,
+        unit: Option<Unit>,
+        description: Option<&str>,
+    ) -> Arc<Timer> {
+        let id = id.into();
         let timer = {
             let inner = self.inner.read().unwrap();
 
-            inner.metrics.get(name).map(|metric| match metric {
+            inner.metrics.get(&id).map(|tracked| match &tracked.entry.metric {
                 Metric::Timer(ref m) => m.clone(),
                 _ => panic!("A metric with same name and different type is already registered."),
             })
@@ -156,9 +293,50 @@ impl MetricsRegistry {
         } else {
             let mut inner_write = self.inner.write().unwrap();
             let timer = Arc::new(Timer::new());
-            inner_write
-                .metrics
-                .insert(name.to_owned(), Metric::Timer(timer.clone()));
+            let entry = MetricEntry::with_metadata(
+                Metric::Timer(timer.clone()),
+                unit,
+                description.map(|d| d.to_owned()),
+            );
+            inner_write.metrics.insert(id, Tracked::new(entry));
+            timer
+        }
+    }
+
+    /// Like [`MetricsRegistry::timer_with`], but also selects the
+    /// `Reservoir` implementation the timer's histogram half samples into
+    /// when it is first created.
+    ///
+    /// Has no effect if a timer with this id already exists, since the
+    /// reservoir is fixed at creation time.
+    pub fn timer_with_reservoir<T: Into<MetricId>>(
+        &self,
+        id: T,
+        reservoir: ReservoirKind,
+        unit: Option<Unit>,
+        description: Option<&str>,
+    ) -> Arc<Timer> {
+        let id = id.into();
+        let timer = {
+            let inner = self.inner.read().unwrap();
+
+            inner.metrics.get(&id).map(|tracked| match &tracked.entry.metric {
+                Metric::Timer(ref m) => m.clone(),
+                _ => panic!("A metric with same name and different type is already registered."),
+            })
+        };
+
+        if let Some(m) = timer {
+            m
+        } else {
+            let mut inner_write = self.inner.write().unwrap();
+            let timer = Arc::new(Timer::with_reservoir(reservoir.build()));
+            let entry = MetricEntry::with_metadata(
+                Metric::Timer(timer.clone()),
+                unit,
+                description.map(|d| d.to_owned()),
+            );
+            inner_write.metrics.insert(id, Tracked::new(entry));
             timer
         }
     }
@@ -166,33 +344,125 @@ impl MetricsRegistry {
     /// Register a `Gauge` with given function.
     ///
     /// The guage will return a value when any reporter wants to fetch data from it.
-    pub fn gauge(&self, name: &str, func: Box<dyn GaugeFn>) {
+    pub fn gauge<T: Into<MetricId>>(&self, id: T, func: Box<dyn GaugeFn>) {
+        self.gauge_with(id, func, None, None);
+    }
+
+    /// Like [`MetricsRegistry::gauge`], but attaches a `Unit` and description
+    /// to the metric.
+    pub fn gauge_with<T: Into<MetricId>>(
+        &self,
+        id: T,
+        func: Box<dyn GaugeFn>,
+        unit: Option<Unit>,
+        description: Option<&str>,
+    ) {
+        let mut inner = self.inner.write().unwrap();
+        let entry = MetricEntry::with_metadata(
+            Metric::Gauge(Arc::new(Gauge::new(func))),
+            unit,
+            description.map(|d| d.to_owned()),
+        );
+        inner.metrics.insert(id.into(), Tracked::new(entry));
+    }
+
+    /// Look up a previously registered `Meter` without creating one, unlike
+    /// [`MetricsRegistry::meter`].
+    pub fn get_meter<T: Into<MetricId>>(&self, id: T) -> Option<Arc<Meter>> {
+        match self.get(id)? {
+            Metric::Meter(m) => Some(m),
+            _ => None,
+        }
+    }
+
+    /// Look up a previously registered `Histogram` without creating one,
+    /// unlike [`MetricsRegistry::histogram`].
+    pub fn get_histogram<T: Into<MetricId>>(&self, id: T) -> Option<Arc<Histogram>> {
+        match self.get(id)? {
+            Metric::Histogram(h) => Some(h),
+            _ => None,
+        }
+    }
+
+    /// Look up a previously registered `Counter` without creating one,
+    /// unlike [`MetricsRegistry::counter`].
+    pub fn get_counter<T: Into<MetricId>>(&self, id: T) -> Option<Arc<Counter>> {
+        match self.get(id)? {
+            Metric::Counter(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Look up a previously registered `Timer` without creating one, unlike
+    /// [`MetricsRegistry::timer`].
+    pub fn get_timer<T: Into<MetricId>>(&self, id: T) -> Option<Arc<Timer>> {
+        match self.get(id)? {
+            Metric::Timer(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    /// Look up a previously registered `Gauge` without creating one.
+    pub fn get_gauge<T: Into<MetricId>>(&self, id: T) -> Option<Arc<Gauge>> {
+        match self.get(id)? {
+            Metric::Gauge(g) => Some(g),
+            _ => None,
+        }
+    }
+
+    /// Look up a previously registered metric of any type without creating
+    /// one.
+    pub fn get<T: Into<MetricId>>(&self, id: T) -> Option<Metric> {
+        let inner = self.inner.read().unwrap();
+        inner.metrics.get(&id.into()).map(|tracked| tracked.entry.metric.clone())
+    }
+
+    /// Remove a single metric by id, returning it if it was registered.
+    ///
+    /// Unlike [`MetricsRegistry::unregister_metrics_set`], which drops a
+    /// whole dynamically-generated set at once, this lets a caller clean up
+    /// one metric it knows it's done with.
+    pub fn remove<T: Into<MetricId>>(&self, id: T) -> Option<Metric> {
         let mut inner = self.inner.write().unwrap();
-        inner
-            .metrics
-            .insert(name.to_owned(), Metric::Gauge(Arc::new(Gauge::new(func))));
+        inner.metrics.remove(&id.into()).map(|tracked| tracked.entry.metric)
+    }
+
+    /// List the ids of every metric currently held directly by the
+    /// registry (not metrics contributed by a registered `MetricsSet`),
+    /// rendered with `MetricId`'s `Display` form (e.g.
+    /// `http.request{method="GET"}`), which is also the key used when the
+    /// registry is serialized. The Prometheus reporter prints sanitized
+    /// names instead (e.g. `http_request`).
+    pub fn names(&self) -> Vec<String> {
+        let inner = self.inner.read().unwrap();
+        inner.metrics.keys().map(|id| id.to_string()).collect()
     }
 
-    /// Returns all the metrics hold in the registry.
+    /// Returns all the metrics hold in the registry, keyed by `MetricId`,
+    /// together with whatever unit/description metadata was attached when
+    /// each metric was created.
     /// Metrics is filtered if a filter is set for this registry.
     ///
     /// This is useful for reporters to fetch all values from the registry.
-    pub fn snapshots(&self) -> HashMap<String, Metric> {
+    pub fn snapshots(&self) -> HashMap<MetricId, MetricEntry> {
         let inner = self.inner.read().unwrap();
         let filter = self.filter.as_ref();
 
         let mut results = HashMap::new();
 
-        for (k, v) in inner.metrics.iter() {
-            if filter.map(|f| f.accept(k, v)).unwrap_or(true) {
-                results.insert(k.to_owned(), v.clone());
+        for (k, tracked) in inner.metrics.iter() {
+            if filter
+                .map(|f| f.accept(k, &tracked.entry.metric))
+                .unwrap_or(true)
+            {
+                results.insert(k.to_owned(), tracked.entry.clone());
             }
         }
         for metrics_set in inner.mset.values() {
             let metrics = metrics_set.get_all();
             for (k, v) in metrics.iter() {
                 if filter.map(|f| f.accept(k, v)).unwrap_or(true) {
-                    results.insert(k.to_owned(), v.clone());
+                    results.insert(k.to_owned(), MetricEntry::new(v.clone()));
                 }
             }
         }
@@ -225,13 +495,90 @@ impl MetricsRegistry {
         let mut inner = self.inner.write().unwrap();
         inner.mset.remove(name);
     }
+
+    /// Remove any metric that hasn't been written to in `timeout`, so
+    /// long-running processes that create high-cardinality metrics (one per
+    /// connection, per request path) don't accumulate entries forever.
+    ///
+    /// `Gauge`s are never evicted by this, since their value is computed on
+    /// demand rather than written to and so they have no notion of "idle".
+    ///
+    /// A metric is only considered idle once its write generation has stayed
+    /// unchanged for at least `timeout` since its baseline (taken when the
+    /// metric is created, and retaken by any call to `evict_idle` that sees
+    /// the generation change, so a metric written after creation survives
+    /// the first call), and only evicted if no caller still holds an `Arc`
+    /// to it (so a handle a caller is actively writing to, but which just
+    /// happens to be quiet, is never pulled out from under them and a
+    /// metric that gets writes again later isn't double-counted).
+    pub fn evict_idle(&self, timeout: Duration) {
+        let now = now_millis();
+        let timeout_millis = timeout.as_millis() as u64;
+        let mut inner = self.inner.write().unwrap();
+
+        inner.metrics.retain(|_, tracked| {
+            let generation = match tracked.entry.metric.generation() {
+                Some(generation) => generation,
+                None => return true,
+            };
+
+            let last_generation = tracked.last_generation.swap(generation, Ordering::Relaxed);
+            if generation != last_generation {
+                tracked.idle_since_millis.store(now, Ordering::Relaxed);
+                return true;
+            }
+
+            let idle_since = tracked.idle_since_millis.load(Ordering::Relaxed);
+            if now.saturating_sub(idle_since) < timeout_millis {
+                return true;
+            }
+
+            tracked.entry.metric.strong_count() > 1
+        });
+    }
+
+    /// Spawn a background thread that calls `evict_idle(idle_timeout)` every
+    /// `interval`. This is opt-in: nothing sweeps idle metrics unless this
+    /// is called. The thread holds only a weak reference to the registry
+    /// and exits on its own once the registry is dropped.
+    pub fn spawn_idle_sweep(self: &Arc<MetricsRegistry>, idle_timeout: Duration, interval: Duration) -> JoinHandle<()> {
+        let weak = Arc::downgrade(self);
+        std::thread::spawn(move || loop {
+            std::thread::sleep(interval);
+            match weak.upgrade() {
+                Some(registry) => registry.evict_idle(idle_timeout),
+                None => break,
+            }
+        })
+    }
+}
+
+#[cfg(feature = "ser")]
+impl Serialize for MetricsRegistry {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let snapshot = self.snapshots();
+        let mut map = serializer.serialize_map(Some(snapshot.len()))?;
+
+        for (k, v) in snapshot.iter() {
+            map.serialize_entry(k, v)?;
+        }
+
+        map.end()
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use std::time::Duration;
+
     use crate::filter::MetricsFilter;
-    use crate::metrics::Metric;
+    use crate::id::MetricId;
+    use crate::metrics::{Metric, ReservoirKind};
     use crate::registry::MetricsRegistry;
+    use crate::unit::Unit;
 
     #[test]
     fn test_metrics_filter() {
@@ -244,8 +591,8 @@ mod test {
 
         struct NameFilter;
         impl MetricsFilter for NameFilter {
-            fn accept(&self, name: &str, _: &Metric) -> bool {
-                name.starts_with("l1")
+            fn accept(&self, id: &MetricId, _: &Metric) -> bool {
+                id.name().starts_with("l1")
             }
         }
 
@@ -254,21 +601,122 @@ mod test {
         let snapshot = registry.snapshots();
         assert_eq!(2, snapshot.len());
     }
-}
 
-#[cfg(feature = "ser")]
-impl Serialize for MetricsRegistry {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        let snapshot = self.snapshots();
-        let mut map = serializer.serialize_map(Some(snapshot.len()))?;
+    #[test]
+    fn test_tagged_metric() {
+        let registry = MetricsRegistry::new();
 
-        for (k, v) in snapshot.iter() {
-            map.serialize_entry(k, v)?;
+        registry
+            .counter(("http.request", &[("method", "GET")][..]))
+            .inc();
+        registry
+            .counter(("http.request", &[("method", "POST")][..]))
+            .inc_by(2);
+
+        let snapshot = registry.snapshots();
+        assert_eq!(2, snapshot.len());
+    }
+
+    #[test]
+    fn test_evict_idle_removes_unreferenced_metric() {
+        let registry = MetricsRegistry::new();
+
+        registry.counter("idle.counter").inc();
+        assert_eq!(1, registry.snapshots().len());
+
+        // First sweep only establishes the generation baseline.
+        registry.evict_idle(Duration::from_millis(0));
+        assert_eq!(1, registry.snapshots().len());
+
+        // Generation hasn't advanced since the baseline and nobody holds a
+        // handle to it, so the second sweep evicts it.
+        registry.evict_idle(Duration::from_millis(0));
+        assert_eq!(0, registry.snapshots().len());
+    }
+
+    #[test]
+    fn test_evict_idle_keeps_referenced_metric() {
+        let registry = MetricsRegistry::new();
+
+        let counter = registry.counter("kept.counter");
+        counter.inc();
+
+        registry.evict_idle(Duration::from_millis(0));
+        registry.evict_idle(Duration::from_millis(0));
+
+        assert_eq!(1, registry.snapshots().len());
+    }
+
+    #[test]
+    fn test_get_does_not_create() {
+        let registry = MetricsRegistry::new();
+
+        assert!(registry.get_counter("missing.counter").is_none());
+        assert_eq!(0, registry.snapshots().len());
+
+        registry.counter("present.counter").inc();
+        assert!(registry.get_counter("present.counter").is_some());
+        assert!(registry.get_meter("present.counter").is_none());
+    }
+
+    #[test]
+    fn test_remove() {
+        let registry = MetricsRegistry::new();
+
+        registry.counter("removable.counter").inc();
+        assert_eq!(1, registry.snapshots().len());
+
+        let removed = registry.remove("removable.counter");
+        assert!(matches!(removed, Some(Metric::Counter(_))));
+        assert_eq!(0, registry.snapshots().len());
+        assert!(registry.remove("removable.counter").is_none());
+    }
+
+    #[test]
+    fn test_names() {
+        let registry = MetricsRegistry::new();
+
+        registry.counter("http.request").inc();
+        registry
+            .counter(("http.request", &[("method", "GET")][..]))
+            .inc();
+
+        let mut names = registry.names();
+        names.sort();
+        assert_eq!(vec!["http.request", "http.request{method=\"GET\"}"], names);
+    }
+
+    #[test]
+    fn test_counter_with_metadata() {
+        let registry = MetricsRegistry::new();
+
+        registry.counter_with("db.bytes", Some(Unit::Bytes), Some("bytes written"));
+
+        let snapshot = registry.snapshots();
+        let id: MetricId = "db.bytes".into();
+        let entry = snapshot.get(&id).unwrap();
+        assert_eq!(Some(Unit::Bytes), entry.unit);
+        assert_eq!(Some("bytes written".to_owned()), entry.description);
+    }
+
+    #[test]
+    fn test_histogram_with_reservoir() {
+        let registry = MetricsRegistry::new();
+
+        let histogram = registry.histogram_with_reservoir(
+            "latency",
+            ReservoirKind::ExpDecay {
+                size: 10,
+                alpha: ReservoirKind::DEFAULT_ALPHA,
+            },
+            None,
+            None,
+        );
+        for i in 1..=20 {
+            histogram.update(i);
         }
 
-        map.end()
+        assert_eq!(20, histogram.count());
+        assert!(registry.get_histogram("latency").is_some());
     }
 }
@@ -0,0 +1,47 @@
+/// The unit a metric's value is measured in, so reporters can render
+/// `# HELP`/unit suffixes and downstream systems can auto-scale values
+/// without guessing from the metric name.
+///
+/// Modeled after the `metrics` crate's `Unit` type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Count,
+    Percent,
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    Nanoseconds,
+    Bytes,
+    Kilobytes,
+    Megabytes,
+    Gigabytes,
+}
+
+impl Unit {
+    /// The canonical, lowercase string form of the unit, e.g. `"bytes"`.
+    pub fn as_canonical_label(&self) -> &'static str {
+        match self {
+            Unit::Count => "count",
+            Unit::Percent => "percent",
+            Unit::Seconds => "seconds",
+            Unit::Milliseconds => "milliseconds",
+            Unit::Microseconds => "microseconds",
+            Unit::Nanoseconds => "nanoseconds",
+            Unit::Bytes => "bytes",
+            Unit::Kilobytes => "kilobytes",
+            Unit::Megabytes => "megabytes",
+            Unit::Gigabytes => "gigabytes",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Unit;
+
+    #[test]
+    fn test_as_canonical_label() {
+        assert_eq!("bytes", Unit::Bytes.as_canonical_label());
+        assert_eq!("count", Unit::Count.as_canonical_label());
+    }
+}
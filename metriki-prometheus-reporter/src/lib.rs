@@ -0,0 +1,5 @@
+pub mod reporter;
+pub mod server;
+
+pub use reporter::PrometheusReporter;
+pub use server::serve;
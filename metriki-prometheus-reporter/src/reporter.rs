@@ -0,0 +1,414 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use metriki_core::id::MetricId;
+use metriki_core::metrics::{Metric, MetricEntry};
+use metriki_core::registry::MetricsRegistry;
+
+/// Percentiles rendered for `Histogram`/`Timer` metrics when no explicit
+/// list is configured on the reporter.
+const DEFAULT_QUANTILES: &[f64] = &[0.5, 0.95, 0.99];
+
+/// Renders a `MetricsRegistry` snapshot as Prometheus text exposition
+/// format, so it can be served behind a `/metrics` scrape endpoint without
+/// writing a custom serializer on top of the serde representation.
+pub struct PrometheusReporter {
+    registry: Arc<MetricsRegistry>,
+    quantiles: Vec<f64>,
+}
+
+impl PrometheusReporter {
+    pub fn new(registry: Arc<MetricsRegistry>) -> PrometheusReporter {
+        PrometheusReporter {
+            registry,
+            quantiles: DEFAULT_QUANTILES.to_vec(),
+        }
+    }
+
+    /// Use a custom set of percentiles (e.g. `[0.5, 0.99, 0.999]`) instead of
+    /// the defaults when rendering histograms and timers.
+    pub fn with_quantiles(mut self, quantiles: Vec<f64>) -> PrometheusReporter {
+        self.quantiles = quantiles;
+        self
+    }
+
+    /// Render the current snapshot of the registry as Prometheus text
+    /// exposition format.
+    pub fn render(&self) -> String {
+        let snapshot = self.registry.snapshots();
+
+        // Group samples by family (the base name a `# HELP`/`# TYPE` header
+        // is emitted for) so a `Histogram`/`Timer`'s `_count`/`_sum`/
+        // `{quantile=...}` series all share a single `summary` header
+        // instead of each claiming its own conflicting `# TYPE` line, even
+        // though a single `Metric` (e.g. a `Timer`) expands into several
+        // printed series.
+        let mut families: BTreeMap<String, Vec<Sample>> = BTreeMap::new();
+
+        for (id, entry) in snapshot.iter() {
+            for sample in self.samples_for(id, entry) {
+                families.entry(sample.family.clone()).or_default().push(sample);
+            }
+        }
+
+        let mut out = String::new();
+        for (family, samples) in families.iter() {
+            let kind = samples[0].kind;
+            let help = samples[0].help.as_deref().unwrap_or("generated by metriki");
+            let _ = writeln!(out, "# HELP {} {}", family, help);
+            let _ = writeln!(out, "# TYPE {} {}", family, kind);
+            for sample in samples {
+                let _ = writeln!(out, "{}{} {}", sample.name, sample.labels, sample.value);
+            }
+        }
+
+        out
+    }
+
+    fn samples_for(&self, id: &MetricId, entry: &MetricEntry) -> Vec<Sample> {
+        let base = sanitize_name(id.name());
+        let labels = render_labels(id.tags(), None);
+        let help = render_help(entry);
+        let metric = &entry.metric;
+
+        let mut samples = self.samples_for_metric(&base, &labels, id, metric);
+        for sample in &mut samples {
+            sample.help = help.clone();
+        }
+        samples
+    }
+
+    fn samples_for_metric(
+        &self,
+        base: &str,
+        labels: &str,
+        id: &MetricId,
+        metric: &Metric,
+    ) -> Vec<Sample> {
+        let base = base.to_owned();
+        let labels = labels.to_owned();
+        match metric {
+            Metric::Counter(c) => vec![Sample::owned(base, "counter", labels, c.count() as f64)],
+            Metric::Gauge(g) => vec![Sample::owned(base, "gauge", labels, g.value())],
+            Metric::Meter(m) => vec![
+                Sample::owned(format!("{}_count", base), "counter", labels.clone(), m.count() as f64),
+                Sample::owned(
+                    format!("{}_m1_rate", base),
+                    "gauge",
+                    labels.clone(),
+                    m.one_minute_rate(),
+                ),
+                Sample::owned(
+                    format!("{}_m5_rate", base),
+                    "gauge",
+                    labels.clone(),
+                    m.five_minute_rate(),
+                ),
+                Sample::owned(format!("{}_m15_rate", base), "gauge", labels, m.fifteen_minute_rate()),
+            ],
+            // A Histogram/Timer's `_count`, `_sum` and `{quantile=...}`
+            // series are all members of the same Prometheus `summary`
+            // family and must share its single `# HELP`/`# TYPE` header
+            // rather than each declaring their own.
+            Metric::Histogram(h) => {
+                let snapshot = h.snapshot();
+                let mut samples = vec![
+                    Sample::member(base.clone(), format!("{}_count", base), "summary", labels.clone(), h.count() as f64),
+                    Sample::member(base.clone(), format!("{}_sum", base), "summary", labels.clone(), h.sum() as f64),
+                ];
+                for q in &self.quantiles {
+                    let quantile_labels = render_labels(id.tags(), Some(*q));
+                    samples.push(Sample::member(base.clone(), base.clone(), "summary", quantile_labels, snapshot.value(*q)));
+                }
+                samples
+            }
+            Metric::Timer(t) => {
+                let snapshot = t.snapshot();
+                let mut samples = vec![
+                    Sample::member(base.clone(), format!("{}_count", base), "summary", labels.clone(), t.count() as f64),
+                    Sample::owned(
+                        format!("{}_m1_rate", base),
+                        "gauge",
+                        labels.clone(),
+                        t.one_minute_rate(),
+                    ),
+                    Sample::owned(
+                        format!("{}_m5_rate", base),
+                        "gauge",
+                        labels.clone(),
+                        t.five_minute_rate(),
+                    ),
+                    Sample::owned(
+                        format!("{}_m15_rate", base),
+                        "gauge",
+                        labels.clone(),
+                        t.fifteen_minute_rate(),
+                    ),
+                    Sample::member(base.clone(), format!("{}_sum", base), "summary", labels.clone(), t.sum() as f64),
+                ];
+                for q in &self.quantiles {
+                    let quantile_labels = render_labels(id.tags(), Some(*q));
+                    samples.push(Sample::member(base.clone(), base.clone(), "summary", quantile_labels, snapshot.value(*q)));
+                }
+                samples
+            }
+        }
+    }
+}
+
+/// One printed Prometheus sample line. `family` is the name a `# HELP`/
+/// `# TYPE` header is grouped and emitted under; `name` is the series name
+/// actually printed on the sample line. They're the same for a standalone
+/// counter/gauge, but a `Histogram`/`Timer`'s `_count`/`_sum`/quantile
+/// series all share one family (the base metric name) while printing under
+/// their own suffixed names.
+struct Sample {
+    family: String,
+    name: String,
+    kind: &'static str,
+    labels: String,
+    value: f64,
+    help: Option<String>,
+}
+
+impl Sample {
+    /// A sample that is its own Prometheus family.
+    fn owned(name: impl Into<String>, kind: &'static str, labels: String, value: f64) -> Sample {
+        let name = name.into();
+        Sample {
+            family: name.clone(),
+            name,
+            kind,
+            labels,
+            value,
+            help: None,
+        }
+    }
+
+    /// A sample that belongs to the `family` name's header rather than
+    /// having its own.
+    fn member(
+        family: impl Into<String>,
+        name: impl Into<String>,
+        kind: &'static str,
+        labels: String,
+        value: f64,
+    ) -> Sample {
+        Sample {
+            family: family.into(),
+            name: name.into(),
+            kind,
+            labels,
+            value,
+            help: None,
+        }
+    }
+}
+
+/// Render the `# HELP` text for a metric: its description if one was
+/// attached, falling back to the unit if only that was set.
+fn render_help(entry: &MetricEntry) -> Option<String> {
+    match (&entry.description, &entry.unit) {
+        (Some(description), _) => Some(description.clone()),
+        (None, Some(unit)) => Some(unit.as_canonical_label().to_owned()),
+        (None, None) => None,
+    }
+}
+
+/// Sanitize a metric name to the Prometheus charset (`[a-zA-Z0-9_:]`), with
+/// a leading underscore inserted if the name would otherwise start with a
+/// digit.
+fn sanitize_name(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == ':' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    if out.chars().next().map(|c| c.is_ascii_digit()).unwrap_or(false) {
+        out.insert(0, '_');
+    }
+
+    out
+}
+
+/// Sanitize a tag key to the Prometheus label-name charset
+/// (`[a-zA-Z0-9_]`, no `:` - that's reserved for recording rules and isn't
+/// a valid label-name character), with a leading underscore inserted if the
+/// name would otherwise start with a digit.
+fn sanitize_label_name(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+
+    if out.chars().next().map(|c| c.is_ascii_digit()).unwrap_or(false) {
+        out.insert(0, '_');
+    }
+
+    out
+}
+
+/// Render the `{k="v",...}` label suffix for a sample, optionally adding a
+/// `quantile` label for histogram/timer percentiles.
+fn render_labels(tags: &[(String, String)], quantile: Option<f64>) -> String {
+    if tags.is_empty() && quantile.is_none() {
+        return String::new();
+    }
+
+    let mut out = String::from("{");
+    for (i, (k, v)) in tags.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let _ = write!(out, "{}=\"{}\"", sanitize_label_name(k), escape_label_value(v));
+    }
+    if let Some(q) = quantile {
+        if !tags.is_empty() {
+            out.push(',');
+        }
+        let _ = write!(out, "quantile=\"{}\"", q);
+    }
+    out.push('}');
+    out
+}
+
+/// Escape a label value per the Prometheus text exposition format: `\` and
+/// `"` are backslash-escaped and newlines become `\n`. Order matters -
+/// backslashes must be escaped first or the escaping of `"`/`\n` would
+/// itself get re-escaped.
+fn escape_label_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use metriki_core::registry::MetricsRegistry;
+
+    #[test]
+    fn test_sanitize_name() {
+        assert_eq!("http_request", sanitize_name("http.request"));
+        assert_eq!("_1xx", sanitize_name("1xx"));
+    }
+
+    #[test]
+    fn test_sanitize_label_name() {
+        assert_eq!("http_method", sanitize_label_name("http.method"));
+        assert_eq!("_1xx", sanitize_label_name("1xx"));
+        // Unlike metric names, `:` isn't part of the label-name charset.
+        assert_eq!("a_b", sanitize_label_name("a:b"));
+    }
+
+    #[test]
+    fn test_escape_label_value() {
+        assert_eq!("a\\\\b\\\"c\\nd", escape_label_value("a\\b\"c\nd"));
+    }
+
+    #[test]
+    fn test_render_sanitizes_dotted_tag_keys() {
+        let registry = MetricsRegistry::arc();
+        registry
+            .counter(("http.request", &[("http.method", "GET")]))
+            .inc();
+
+        let reporter = PrometheusReporter::new(registry);
+        let rendered = reporter.render();
+
+        assert!(rendered.contains("http_request{http_method=\"GET\"} 1"));
+    }
+
+    #[test]
+    fn test_render_escapes_tag_values() {
+        let registry = MetricsRegistry::arc();
+        registry
+            .counter(("http.request", &[("path", "a\\b\"c")]))
+            .inc();
+
+        let reporter = PrometheusReporter::new(registry);
+        let rendered = reporter.render();
+
+        assert!(rendered.contains("http_request{path=\"a\\\\b\\\"c\"} 1"));
+    }
+
+    #[test]
+    fn test_render_counter() {
+        let registry = MetricsRegistry::arc();
+        registry.counter("http.request").inc_by(3);
+
+        let reporter = PrometheusReporter::new(registry);
+        let rendered = reporter.render();
+
+        assert!(rendered.contains("# TYPE http_request counter"));
+        assert!(rendered.contains("http_request 3"));
+    }
+
+    #[test]
+    fn test_render_meter_expands_series() {
+        let registry = MetricsRegistry::arc();
+        registry.meter("http.request").mark();
+
+        let reporter = PrometheusReporter::new(registry);
+        let rendered = reporter.render();
+
+        assert!(rendered.contains("http_request_count 1"));
+        assert!(rendered.contains("# TYPE http_request_m1_rate gauge"));
+    }
+
+    #[test]
+    fn test_render_tags_as_labels() {
+        let registry = MetricsRegistry::arc();
+        registry
+            .counter(("http.request", &[("method", "GET")][..]))
+            .inc();
+
+        let reporter = PrometheusReporter::new(registry);
+        let rendered = reporter.render();
+
+        assert!(rendered.contains("http_request{method=\"GET\"} 1"));
+    }
+
+    #[test]
+    fn test_render_histogram_as_single_summary_family() {
+        let registry = MetricsRegistry::arc();
+        let histogram = registry.histogram("request.size");
+        histogram.update(10);
+        histogram.update(20);
+
+        let reporter = PrometheusReporter::new(registry);
+        let rendered = reporter.render();
+
+        // Exactly one HELP/TYPE pair for the whole family: `_count`/`_sum`
+        // must not claim their own conflicting `# TYPE` lines.
+        assert_eq!(1, rendered.matches("# TYPE request_size").count());
+        assert!(rendered.contains("# TYPE request_size summary"));
+        assert!(rendered.contains("request_size_count 2"));
+        assert!(rendered.contains("request_size_sum 30"));
+        assert!(rendered.contains("request_size{quantile=\"0.5\"}"));
+        assert!(rendered.contains("request_size{quantile=\"0.99\"}"));
+    }
+
+    #[test]
+    fn test_render_timer_as_single_summary_family() {
+        let registry = MetricsRegistry::arc();
+        let timer = registry.timer("request.latency");
+        timer.update(std::time::Duration::from_nanos(10));
+        timer.update(std::time::Duration::from_nanos(20));
+
+        let reporter = PrometheusReporter::new(registry);
+        let rendered = reporter.render();
+
+        assert_eq!(1, rendered.matches("# TYPE request_latency ").count());
+        assert!(rendered.contains("# TYPE request_latency summary"));
+        assert!(rendered.contains("request_latency_count 2"));
+        assert!(rendered.contains("request_latency_sum 30"));
+        assert!(rendered.contains("request_latency{quantile=\"0.5\"}"));
+        assert!(rendered.contains("# TYPE request_latency_m1_rate gauge"));
+    }
+}
@@ -0,0 +1,50 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::thread;
+
+use crate::reporter::PrometheusReporter;
+
+/// Serve the reporter's rendered snapshot over HTTP at `GET /metrics`.
+///
+/// This blocks the calling thread accepting connections; spawn it on a
+/// dedicated thread if the caller needs to keep running other work.
+pub fn serve(reporter: Arc<PrometheusReporter>, addr: impl ToSocketAddrs) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let reporter = reporter.clone();
+        thread::spawn(move || {
+            let _ = handle_connection(stream, &reporter);
+        });
+    }
+
+    Ok(())
+}
+
+fn handle_connection(mut stream: TcpStream, reporter: &PrometheusReporter) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    if request_line.starts_with("GET /metrics") {
+        let body = reporter.render();
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )?;
+    } else {
+        let body = "not found";
+        write!(
+            stream,
+            "HTTP/1.1 404 Not Found\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )?;
+    }
+
+    Ok(())
+}